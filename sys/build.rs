@@ -4,7 +4,7 @@ extern crate bindgen;
 
 use cmake::Config;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn main() {
     let target = env::var("TARGET").unwrap();
@@ -73,8 +73,22 @@ fn main() {
         }
     }
     println!("cargo:rerun-if-changed=wrapper.h");
+    println!("cargo:rerun-if-env-changed=WHISPER_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=WHISPER_INCLUDE_DIR");
+    println!("cargo:rerun-if-env-changed=CROSS_SYSROOT");
+    println!("cargo:rerun-if-env-changed=CROSS_TOOLCHAIN_FILE");
 
     let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    // If a prebuilt whisper is available (pointed to directly, or found via
+    // pkg-config under the `system` feature), link against it and skip the
+    // bundled cmake build entirely. This lets distro packagers and CI caches
+    // reuse a single shared whisper build across many crates.
+    if let Some(include_dirs) = link_system_whisper() {
+        generate_bindings(&out, &include_dirs, &target);
+        return;
+    }
+
     let whisper_root = out.join("whisper.cpp/");
 
     if !whisper_root.exists() {
@@ -88,31 +102,7 @@ fn main() {
         });
     }
 
-    if env::var("WHISPER_DONT_GENERATE_BINDINGS").is_ok() {
-        let _: u64 = std::fs::copy("src/bindings.rs", out.join("bindings.rs"))
-            .expect("Failed to copy bindings.rs");
-    } else {
-        let bindings = bindgen::Builder::default()
-            .header("wrapper.h")
-            .clang_arg("-I./whisper.cpp")
-            .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
-            .generate();
-
-        match bindings {
-            Ok(b) => {
-                let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
-                b.write_to_file(out_path.join("bindings.rs"))
-                    .expect("Couldn't write bindings!");
-            }
-            Err(e) => {
-                println!("cargo:warning=Unable to generate bindings: {}", e);
-                println!("cargo:warning=Using bundled bindings.rs, which may be out of date");
-                // copy src/bindings.rs to OUT_DIR
-                std::fs::copy("src/bindings.rs", out.join("bindings.rs"))
-                    .expect("Unable to copy bindings.rs");
-            }
-        }
-    };
+    generate_bindings(&out, &[whisper_root.clone()], &target);
 
     // stop if we're on docs.rs
     if env::var("DOCS_RS").is_ok() {
@@ -121,9 +111,40 @@ fn main() {
 
     let mut config = Config::new(&whisper_root);
 
+    // Cross-compiling: tell cmake about the target platform instead of letting it infer the
+    // host's, and forward an optional toolchain file / sysroot for the cross toolchain.
+    if target != env::var("HOST").unwrap() {
+        let cmake_system_name = match env::var("CARGO_CFG_TARGET_OS").unwrap().as_str() {
+            "android" => "Android",
+            "linux" => "Linux",
+            "windows" => "Windows",
+            "macos" => "Darwin",
+            "ios" => "iOS",
+            other => other,
+        };
+        config.define("CMAKE_SYSTEM_NAME", cmake_system_name);
+        config.define(
+            "CMAKE_SYSTEM_PROCESSOR",
+            env::var("CARGO_CFG_TARGET_ARCH").unwrap(),
+        );
+        if let Ok(toolchain_file) = env::var("CROSS_TOOLCHAIN_FILE") {
+            config.define("CMAKE_TOOLCHAIN_FILE", toolchain_file);
+        }
+        if let Ok(sysroot) = env::var("CROSS_SYSROOT") {
+            config.define("CMAKE_SYSROOT", sysroot);
+        }
+    }
+
     config
         .profile("Release")
-        .define("BUILD_SHARED_LIBS", "OFF")
+        .define(
+            "BUILD_SHARED_LIBS",
+            if cfg!(feature = "dynamic-link") {
+                "ON"
+            } else {
+                "OFF"
+            },
+        )
         .define("WHISPER_ALL_WARNINGS", "OFF")
         .define("WHISPER_ALL_WARNINGS_3RD_PARTY", "OFF")
         .define("WHISPER_BUILD_TESTS", "OFF")
@@ -172,15 +193,29 @@ fn main() {
         config.define("CMAKE_BUILD_TYPE", "RelWithDebInfo");
     }
 
-    // Allow passing any WHISPER cmake flag
+    // Allow passing any WHISPER cmake flag, except the internal vars `link_system_whisper`
+    // reads to locate a prebuilt whisper (not real cmake options).
     for (key, value) in env::vars() {
-        if key.starts_with("WHISPER_") && key != "WHISPER_DONT_GENERATE_BINDINGS" {
+        if key.starts_with("WHISPER_") && key != "WHISPER_LIB_DIR" && key != "WHISPER_INCLUDE_DIR"
+        {
             config.define(&key, &value);
         }
     }
 
     let destination = config.build();
 
+    // Link the extra system libs the bundled whisper archive needs per-platform, as cc-rs/std's
+    // own build scripts do per target rather than assuming the host's defaults apply. Only
+    // relevant here, since a system-provided whisper (see `link_system_whisper`) already carries
+    // its own transitive link requirements and returns before this point is ever reached.
+    if target.contains("android") {
+        println!("cargo:rustc-link-lib=log");
+    } else if target.contains("linux") {
+        println!("cargo:rustc-link-lib=dl");
+        println!("cargo:rustc-link-lib=rt");
+        println!("cargo:rustc-link-lib=pthread");
+    }
+
     if target.contains("window") && !target.contains("gnu") {
         println!(
             "cargo:rustc-link-search={}",
@@ -190,12 +225,170 @@ fn main() {
         println!("cargo:rustc-link-search={}", out.join("build").display());
     }
     println!("cargo:rustc-link-search=native={}", destination.display());
-    println!("cargo:rustc-link-lib=static=whisper");
+
+    if cfg!(feature = "dynamic-link") {
+        println!("cargo:rustc-link-lib=dylib=whisper");
+        // The built .so/.dylib isn't installed anywhere on the system search path, so point
+        // the runtime loader at it directly. On Windows the loader instead looks next to the
+        // executable or on PATH, so the DLL built under build/Release (MSVC) or build/ (GNU)
+        // must be copied there.
+        if target.contains("windows") {
+            let dll_dir = if target.contains("window") && !target.contains("gnu") {
+                out.join("build").join("Release")
+            } else {
+                out.join("build")
+            };
+            println!(
+                "cargo:warning=dynamic-link: copy whisper.dll from {} next to your executable or onto PATH",
+                dll_dir.display()
+            );
+        } else {
+            println!(
+                "cargo:warning=dynamic-link: rpath points at {}, under OUT_DIR; it only keeps working as long as that build output isn't moved or `cargo clean`ed, so install libwhisper somewhere permanent for anything beyond local development",
+                out.join("build").display()
+            );
+            println!(
+                "cargo:rustc-link-arg=-Wl,-rpath,{}",
+                out.join("build").display()
+            );
+        }
+    } else {
+        println!("cargo:rustc-link-lib=static=whisper");
+    }
 
     // for whatever reason this file is generated during build and triggers cargo complaining
     _ = std::fs::remove_file("bindings/javascript/package.json");
 }
 
+// Try to link against a whisper/ggml that's already installed on the system
+// instead of building the bundled copy. Returns the include directories to
+// point bindgen at when a system library was found (possibly empty, if the
+// library's headers already live on the compiler's default search path).
+//
+// Resolution order:
+//   1. `WHISPER_LIB_DIR`/`WHISPER_INCLUDE_DIR` env vars, set directly by the
+//      user (the `ROCKSDB_INCLUDE_DIR`-style override).
+//   2. pkg-config, when the `system` feature is enabled.
+fn link_system_whisper() -> Option<Vec<PathBuf>> {
+    if let Ok(lib_dir) = env::var("WHISPER_LIB_DIR") {
+        println!("cargo:rustc-link-search=native={}", lib_dir);
+        println!("cargo:rustc-link-lib=whisper");
+
+        let include_dir = match env::var("WHISPER_INCLUDE_DIR") {
+            Ok(include_dir) => PathBuf::from(include_dir),
+            Err(_) => PathBuf::from(&lib_dir).join("../include"),
+        };
+        return Some(vec![include_dir]);
+    }
+
+    if cfg!(feature = "system") {
+        // `cargo_metadata(false)`: we emit the link directives ourselves from `library.libs`
+        // below, since whisper.cpp's `.pc` file can list more than one lib (whisper, ggml,
+        // ggml-base, ...) and pkg-config's own metadata emission would otherwise double up
+        // with (or fall short of) that.
+        if let Ok(library) = pkg_config::Config::new()
+            .cargo_metadata(false)
+            .probe("whisper")
+        {
+            for path in &library.link_paths {
+                println!("cargo:rustc-link-search=native={}", path.display());
+            }
+            for lib in &library.libs {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+
+            // Found via pkg-config: always report success, even with no `-I` (common when
+            // headers install to a default search path), so the caller doesn't fall through
+            // to the bundled cmake build and link whisper twice.
+            return Some(library.include_paths);
+        }
+    }
+
+    None
+}
+
+// Target tuple used to name pregenerated binding files, e.g. `src/bindings/x86_64-linux-gnu.rs`.
+// Built from the `CARGO_CFG_TARGET_*` vars cargo sets for the actual compilation target, which
+// leave out the vendor field that `TARGET`'s full triple carries (e.g. `x86_64-unknown-linux-gnu`).
+fn bindings_target_name() -> String {
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    if env.is_empty() {
+        format!("{}-{}", arch, os)
+    } else {
+        format!("{}-{}-{}", arch, os, env)
+    }
+}
+
+fn generate_bindings(out_dir: &Path, include_dirs: &[PathBuf], target: &str) {
+    let pregenerated = PathBuf::from("src/bindings").join(format!("{}.rs", bindings_target_name()));
+
+    if !cfg!(feature = "bindgen") {
+        let bindings_src = if pregenerated.exists() {
+            pregenerated
+        } else {
+            println!(
+                "cargo:warning=No pregenerated bindings for target `{}`, falling back to src/bindings.rs",
+                bindings_target_name()
+            );
+            PathBuf::from("src/bindings.rs")
+        };
+        std::fs::copy(&bindings_src, out_dir.join("bindings.rs"))
+            .unwrap_or_else(|e| panic!("Failed to copy {}: {}", bindings_src.display(), e));
+        return;
+    }
+
+    let mut builder = bindgen::Builder::default()
+        .header("wrapper.h")
+        // Only emit items declared in whisper/ggml headers themselves, so system headers
+        // pulled in transitively (e.g. stdlib/libc) don't leak into the bindings and cause
+        // things like duplicate/incompatible `max_align_t` definitions across platforms.
+        .allowlist_file(".*[/\\\\](whisper|ggml)[^/\\\\]*\\.h")
+        .ctypes_prefix("libc")
+        .size_t_is_usize(true)
+        .derive_debug(true)
+        .derive_partialeq(true)
+        .derive_eq(true)
+        .derive_hash(true)
+        .derive_default(true)
+        .merge_extern_blocks(true)
+        .sort_semantically(true)
+        // Parse headers as the actual compilation target, not the host clang defaults to,
+        // so cross-compiles (aarch64-android, musl, ...) get correctly sized/laid-out types.
+        .clang_arg(format!("--target={}", target));
+    for include_dir in include_dirs {
+        builder = builder.clang_arg(format!("-I{}", include_dir.display()));
+    }
+    if let Ok(sysroot) = env::var("CROSS_SYSROOT") {
+        builder = builder.clang_arg(format!("--sysroot={}", sysroot));
+    }
+    let bindings = builder
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
+        .generate();
+
+    match bindings {
+        Ok(b) => {
+            b.write_to_file(out_dir.join("bindings.rs"))
+                .expect("Couldn't write bindings!");
+
+            if cfg!(feature = "update-bindings") {
+                std::fs::create_dir_all("src/bindings").expect("Failed to create src/bindings");
+                b.write_to_file(&pregenerated).unwrap_or_else(|e| {
+                    panic!("Failed to write {}: {}", pregenerated.display(), e)
+                });
+            }
+        }
+        Err(e) => {
+            println!("cargo:warning=Unable to generate bindings: {}", e);
+            println!("cargo:warning=Using bundled bindings.rs, which may be out of date");
+            // copy src/bindings.rs to OUT_DIR
+            std::fs::copy("src/bindings.rs", out_dir.join("bindings.rs"))
+                .expect("Unable to copy bindings.rs");
+        }
+    }
+}
+
 // From https://github.com/alexcrichton/cc-rs/blob/fba7feded71ee4f63cfe885673ead6d7b4f2f454/src/lib.rs#L2462
 fn get_cpp_link_stdlib(target: &str) -> Option<&'static str> {
     if target.contains("msvc") {